@@ -16,6 +16,10 @@ use graphql_client::GraphQLQuery;
 use itertools::Itertools as _;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::Semaphore;
 
 #[derive(Serialize, Deserialize, Debug)]
 struct GitObjectID(String);
@@ -36,35 +40,97 @@ pub struct Start;
 )]
 pub struct Cont;
 
+/// Number of attempts a request makes before a rate-limit backoff gives up.
+const MAX_ATTEMPTS: u32 = 5;
+
 pub struct Client {
     inner: reqwest::Client,
     url: String,
-    token: Option<String>,
+    rest_url: String,
+    auth: Auth,
+    /// Global permit pool bounding the number of in-flight requests across the
+    /// whole recursion, not just within a single directory.
+    semaphore: Arc<Semaphore>,
+}
+
+/// How the `Client` authenticates to GitHub.
+enum Auth {
+    /// Unauthenticated (subject to the anonymous rate limit).
+    Anonymous,
+    /// A static personal access token.
+    Token(String),
+    /// A GitHub App installation, minting and refreshing its own tokens.
+    App(AppAuth),
+}
+
+/// GitHub App credentials plus the most recently minted installation token.
+struct AppAuth {
+    app_id: String,
+    installation_id: String,
+    key: jsonwebtoken::EncodingKey,
+    cached: Mutex<Option<(String, SystemTime)>>,
+}
+
+#[derive(Serialize)]
+struct AppClaims {
+    iat: u64,
+    exp: u64,
+    iss: String,
+}
+
+#[derive(Deserialize)]
+struct InstallationToken {
+    token: String,
+    expires_at: String,
 }
 
 impl Client {
+    /// Send `builder` (after applying auth), acquiring a concurrency permit and
+    /// retrying with backoff when GitHub reports a rate limit (403/429).
+    async fn send(&self, builder: reqwest::RequestBuilder) -> anyhow::Result<reqwest::Response> {
+        for attempt in 1..=MAX_ATTEMPTS {
+            let permit = self.semaphore.acquire().await?;
+            let builder = builder
+                .try_clone()
+                .context("request body is not cloneable")?;
+            let response = match self.bearer().await? {
+                Some(it) => builder.bearer_auth(it),
+                None => builder,
+            }
+            .send()
+            .await?;
+
+            match response.error_for_status_ref() {
+                Ok(_) => return Ok(response),
+                Err(e) => {
+                    let rate_limited = matches!(response.status().as_u16(), 403 | 429);
+                    if rate_limited && attempt < MAX_ATTEMPTS {
+                        let delay = retry_after(response.headers())
+                            .unwrap_or_else(|| Duration::from_secs(1 << attempt.min(6)));
+                        drop(permit);
+                        tokio::time::sleep(delay).await;
+                        continue;
+                    }
+                    bail!("{}\n\n{}", e, response.text().await?)
+                }
+            }
+        }
+        unreachable!("loop returns or bails on the final attempt")
+    }
+
     async fn query<T: GraphQLQuery>(
         &self,
         params: T::Variables,
     ) -> anyhow::Result<T::ResponseData> {
-        let builder = self.inner.post(&self.url).json(&T::build_query(params));
-        let response = match &self.token {
-            Some(it) => builder.bearer_auth(it),
-            None => builder,
-        }
-        .send()
-        .await?;
+        let response = self
+            .send(self.inner.post(&self.url).json(&T::build_query(params)))
+            .await?;
 
         let graphql_client::Response {
             data,
             errors,
             extensions: _,
-        } = match response.error_for_status_ref() {
-            Ok(_) => response.json().await?,
-            Err(e) => {
-                bail!("{}\n\n{}", e, response.text().await?)
-            }
-        };
+        } = response.json().await?;
 
         if errors.as_ref().is_some_and(|it| !it.is_empty()) {
             bail!("query errors: {}", errors.into_iter().flatten().join(", "))
@@ -72,6 +138,186 @@ impl Client {
 
         data.context("query response has no `data` member")
     }
+
+    /// Fetch the raw bytes of a blob via the REST API, used for binary files
+    /// where the GraphQL `Blob.text` field is null.
+    async fn get_bytes(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        oid: &GitObjectID,
+    ) -> anyhow::Result<Vec<u8>> {
+        let url = format!(
+            "{}/repos/{}/{}/git/blobs/{}",
+            self.rest_url, repo_owner, repo_name, oid.0
+        );
+        let response = self
+            .send(
+                self.inner
+                    .get(&url)
+                    .header(reqwest::header::ACCEPT, "application/vnd.github.raw"),
+            )
+            .await?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    /// The bearer token to present on the next request, minting or refreshing a
+    /// GitHub App installation token on demand.
+    async fn bearer(&self) -> anyhow::Result<Option<String>> {
+        match &self.auth {
+            Auth::Anonymous => Ok(None),
+            Auth::Token(it) => Ok(Some(it.clone())),
+            Auth::App(app) => Ok(Some(self.installation_token(app).await?)),
+        }
+    }
+
+    /// Return a cached installation token, refreshing it via a freshly-signed
+    /// JWT when it is absent or within a minute of expiring.
+    async fn installation_token(&self, app: &AppAuth) -> anyhow::Result<String> {
+        let now = SystemTime::now();
+        if let Some((token, expiry)) = app.cached.lock().unwrap().as_ref() {
+            if *expiry > now + Duration::from_secs(60) {
+                return Ok(token.clone());
+            }
+        }
+
+        let unix = now.duration_since(UNIX_EPOCH)?.as_secs();
+        let claims = AppClaims {
+            iat: unix - 60,
+            exp: unix + 600,
+            iss: app.app_id.clone(),
+        };
+        let jwt = jsonwebtoken::encode(
+            &jsonwebtoken::Header::new(jsonwebtoken::Algorithm::RS256),
+            &claims,
+            &app.key,
+        )?;
+
+        let url = format!(
+            "{}/app/installations/{}/access_tokens",
+            self.rest_url, app.installation_id
+        );
+        let response = self
+            .inner
+            .post(&url)
+            .bearer_auth(&jwt)
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+            .send()
+            .await?;
+        let InstallationToken { token, expires_at } = match response.error_for_status_ref() {
+            Ok(_) => response.json().await?,
+            Err(e) => bail!("{}\n\n{}", e, response.text().await?),
+        };
+
+        let expiry = time::OffsetDateTime::parse(
+            &expires_at,
+            &time::format_description::well_known::Rfc3339,
+        )
+        .map(SystemTime::from)
+        .unwrap_or(now + Duration::from_secs(600));
+        *app.cached.lock().unwrap() = Some((token.clone(), expiry));
+        Ok(token)
+    }
+}
+
+/// How long to wait before retrying a rate-limited response, derived from the
+/// `Retry-After` header or the `X-RateLimit-Reset`/`-Remaining` pair.
+fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|it| it.to_str().ok())
+            .and_then(|it| it.trim().parse::<u64>().ok())
+    };
+    if let Some(secs) = header("retry-after") {
+        return Some(Duration::from_secs(secs));
+    }
+    if header("x-ratelimit-remaining") == Some(0) {
+        let reset = header("x-ratelimit-reset")?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()?
+            .as_secs();
+        return Some(Duration::from_secs(reset.saturating_sub(now)));
+    }
+    None
+}
+
+/// Name of the manifest written into the local destination in `--incremental`
+/// mode, mapping each downloaded file's path (relative to the destination) to
+/// the git `oid` and `mode` it was fetched at.
+const MANIFEST_NAME: &str = ".github-get-folder.json";
+
+/// The git mode of a tree (directory); entries with any other mode are checked
+/// against the manifest as files.
+const TREE_MODE: i64 = 0o040000;
+
+/// A manifest entry: enough to decide whether a file can be left untouched. The
+/// `mode` is kept alongside the `oid` so that a content-identical file whose
+/// mode changed (e.g. newly executable, or regular↔symlink) is still rewritten.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+struct Record {
+    oid: String,
+    mode: i64,
+}
+
+/// Bookkeeping for `--incremental` runs: the manifest from the previous sync
+/// and the manifest being accumulated by the current walk.
+struct Incremental {
+    root: Utf8PathBuf,
+    old: BTreeMap<Utf8PathBuf, Record>,
+    new: Mutex<BTreeMap<Utf8PathBuf, Record>>,
+}
+
+impl Incremental {
+    /// Load the manifest left behind by a previous run, if any.
+    async fn load(root: &Utf8Path) -> anyhow::Result<Self> {
+        let old = match tokio::fs::read(root.join(MANIFEST_NAME)).await {
+            Ok(bytes) => serde_json::from_slice(&bytes).context("malformed oid manifest")?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            root: root.to_owned(),
+            old,
+            new: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    fn relative(&self, path: &Utf8Path) -> Utf8PathBuf {
+        path.strip_prefix(&self.root).unwrap_or(path).to_owned()
+    }
+
+    /// Whether the blob at `rel` can be left untouched because both its recorded
+    /// oid and mode are unchanged.
+    fn unchanged(&self, rel: &Utf8Path, oid: &GitObjectID, mode: i64) -> bool {
+        self.old
+            .get(rel)
+            .is_some_and(|it| it.oid == oid.0 && it.mode == mode)
+    }
+
+    fn record(&self, rel: Utf8PathBuf, oid: String, mode: i64) {
+        self.new.lock().unwrap().insert(rel, Record { oid, mode });
+    }
+
+    /// Persist the freshly-walked manifest and remove local files whose path no
+    /// longer appears in the tree.
+    async fn commit(self) -> anyhow::Result<()> {
+        let new = self.new.into_inner().unwrap();
+        for stale in self.old.keys().filter(|it| !new.contains_key(*it)) {
+            match tokio::fs::remove_file(self.root.join(stale)).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        tokio::fs::write(
+            self.root.join(MANIFEST_NAME),
+            serde_json::to_vec_pretty(&new)?,
+        )
+        .await?;
+        Ok(())
+    }
 }
 
 async fn get(
@@ -80,7 +326,24 @@ async fn get(
     repo_owner: &str,
     local_path: Cow<'_, Utf8Path>,
     oid: GitObjectID,
+    mode: i64,
+    incremental: Option<&Incremental>,
 ) -> anyhow::Result<()> {
+    // An unchanged blob keeps the same content-hash oid, so in incremental mode
+    // we can skip its `Cont` query entirely when the manifest already has it.
+    if let Some(inc) = incremental {
+        if mode != TREE_MODE {
+            let rel = inc.relative(&local_path);
+            if inc.unchanged(&rel, &oid, mode)
+                && tokio::fs::try_exists(local_path.as_std_path()).await?
+            {
+                println!("skip {}", local_path);
+                inc.record(rel, oid.0, mode);
+                return Ok(());
+            }
+        }
+    }
+
     match client
         .query::<Cont>(cont::Variables {
             repo_name: repo_name.into(),
@@ -92,25 +355,32 @@ async fn get(
         .and_then(|it| it.object)
         .context("incomplete response")?
     {
-        ContRepositoryObject::Blob(ContRepositoryObjectOnBlob { text }) => {
+        ContRepositoryObject::Blob(ContRepositoryObjectOnBlob {
+            text,
+            is_binary,
+            oid,
+        }) => {
             println!("blob {}", local_path);
-            tokio::fs::write(
-                local_path.as_std_path(),
-                text.context("binary blobs are not supported")?,
-            )
-            .await?;
+            let bytes = match text {
+                Some(text) if !is_binary.unwrap_or(false) => text.into_bytes(),
+                _ => client.get_bytes(repo_owner, repo_name, &oid).await?,
+            };
+            write_blob(local_path.as_std_path(), bytes, mode).await?;
+            if let Some(inc) = incremental {
+                inc.record(inc.relative(&local_path), oid.0, mode);
+            }
         }
         ContRepositoryObject::Tree(ContRepositoryObjectOnTree { entries }) => {
             println!("tree {}", local_path);
             tree(
                 local_path,
-                entries
-                    .into_iter()
-                    .flatten()
-                    .map(|ContRepositoryObjectOnTreeEntries { name, oid }| (name, oid)),
+                entries.into_iter().flatten().map(
+                    |ContRepositoryObjectOnTreeEntries { name, oid, mode }| (name, oid, mode),
+                ),
                 client,
                 repo_name,
                 repo_owner,
+                incremental,
             )
             .await?;
         }
@@ -122,22 +392,27 @@ async fn get(
 
 async fn tree(
     local_path: Cow<'_, Utf8Path>,
-    entries: impl IntoIterator<Item = (String, GitObjectID)>,
+    entries: impl IntoIterator<Item = (String, GitObjectID, i64)>,
     client: &Client,
     repo_name: &str,
     repo_owner: &str,
+    incremental: Option<&Incremental>,
 ) -> anyhow::Result<()> {
     tokio::fs::create_dir_all(local_path.as_std_path()).await?;
     let entries = entries.into_iter().collect::<Vec<_>>();
+    // Concurrency is bounded globally by `client.semaphore`, so we can eagerly
+    // hand every entry to the executor and let the permits throttle fan-out.
     let concurrency = entries.len().saturating_add(1);
     stream::iter(entries)
-        .map(|(name, oid)| {
+        .map(|(name, oid, mode)| {
             get(
                 client,
                 repo_name,
                 repo_owner,
                 local_path.join(name).into(),
                 oid,
+                mode,
+                incremental,
             )
         })
         .buffer_unordered(concurrency)
@@ -145,6 +420,47 @@ async fn tree(
         .await
 }
 
+/// Write a blob to `path`, honouring the git file `mode`: the executable bit
+/// (`0o100755`) is mapped to filesystem permissions and symlinks (`0o120000`)
+/// are created from the blob contents, which hold the link target.
+#[cfg(unix)]
+async fn write_blob(path: &std::path::Path, bytes: Vec<u8>, mode: i64) -> anyhow::Result<()> {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    const SYMLINK: i64 = 0o120000;
+    const EXECUTABLE: i64 = 0o100755;
+
+    // Re-syncs (plain, `--incremental`, or `serve`) write over an existing
+    // tree, so clear any prior entry first: `symlink` would fail with `EEXIST`,
+    // and a `write` onto an existing symlink would follow it and clobber the
+    // target rather than replacing the link.
+    match tokio::fs::symlink_metadata(path).await {
+        Ok(_) => tokio::fs::remove_file(path).await?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    if mode == SYMLINK {
+        let target = String::from_utf8(bytes).context("symlink target is not valid UTF-8")?;
+        tokio::fs::symlink(target, path).await?;
+        return Ok(());
+    }
+
+    tokio::fs::write(path, bytes).await?;
+    if mode == EXECUTABLE {
+        let mut perms = tokio::fs::metadata(path).await?.permissions();
+        perms.set_mode(0o755);
+        tokio::fs::set_permissions(path, perms).await?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn write_blob(path: &std::path::Path, bytes: Vec<u8>, _mode: i64) -> anyhow::Result<()> {
+    tokio::fs::write(path, bytes).await?;
+    Ok(())
+}
+
 #[derive(Parser)]
 struct Args {
     /// The `rust-lang` in `https://github.com/rust-lang/rust`.
@@ -159,6 +475,16 @@ struct Args {
     local: Utf8PathBuf,
     #[arg(long, default_value = "https://api.github.com/graphql")]
     endpoint: String,
+    #[arg(long, default_value = "https://api.github.com")]
+    rest_endpoint: String,
+    /// Maximum number of concurrent requests to GitHub.
+    #[arg(long, default_value_t = 8)]
+    concurrency: usize,
+    /// Only re-download files whose oid or mode changed since the last run (or
+    /// that are missing locally), using a manifest stored in the local
+    /// destination.
+    #[arg(long)]
+    incremental: bool,
     #[arg(
         long,
         short,
@@ -167,6 +493,35 @@ struct Args {
         hide_env_values = true
     )]
     token: Option<String>,
+    /// GitHub App id, for installation-token auth (an alternative to `--token`).
+    /// All three of `--app-id`, `--private-key` and `--installation-id` must be
+    /// given together.
+    #[arg(long, requires_all = ["private_key", "installation_id"])]
+    app_id: Option<String>,
+    /// Path to the GitHub App private key in PEM format.
+    #[arg(long, value_name = "PEM", requires_all = ["app_id", "installation_id"])]
+    private_key: Option<Utf8PathBuf>,
+    /// GitHub App installation id.
+    #[arg(long, requires_all = ["app_id", "private_key"])]
+    installation_id: Option<String>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Run as a service, re-syncing whenever a GitHub `push` webhook arrives.
+    Serve(ServeArgs),
+}
+
+#[derive(clap::Args)]
+struct ServeArgs {
+    /// Address to bind the webhook listener to.
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    listen: std::net::SocketAddr,
+    /// Shared secret used to verify the `X-Hub-Signature-256` header.
+    #[arg(long, env = "GITHUB_WEBHOOK_SECRET", hide_env_values = true)]
+    webhook_secret: String,
 }
 
 fn main() -> anyhow::Result<()> {
@@ -187,12 +542,38 @@ async fn _main() -> anyhow::Result<()> {
         remote: remote_path,
         local: local_path,
         endpoint,
+        rest_endpoint,
+        concurrency,
+        incremental,
         token,
+        app_id,
+        private_key,
+        installation_id,
+        command,
     } = Args::parse();
+    let auth = match (app_id, private_key, installation_id) {
+        (Some(app_id), Some(private_key), Some(installation_id)) => {
+            let pem = tokio::fs::read(&private_key).await?;
+            let key = jsonwebtoken::EncodingKey::from_rsa_pem(&pem)
+                .context("invalid GitHub App private key")?;
+            Auth::App(AppAuth {
+                app_id,
+                installation_id,
+                key,
+                cached: Mutex::new(None),
+            })
+        }
+        _ => match token {
+            Some(token) => Auth::Token(token),
+            None => Auth::Anonymous,
+        },
+    };
     let client = Client {
         inner: reqwest::Client::builder().user_agent(USER_AGENT).build()?,
         url: endpoint,
-        token,
+        rest_url: rest_endpoint,
+        auth,
+        semaphore: Arc::new(Semaphore::new(concurrency)),
     };
     let remote_path = match remote_path.is_absolute() {
         true => remote_path
@@ -206,31 +587,91 @@ async fn _main() -> anyhow::Result<()> {
         false => remote_path,
     };
 
+    match command {
+        None => {
+            run_sync(
+                &client,
+                &repo_owner,
+                &repo_name,
+                &commit_ish,
+                &remote_path,
+                &local_path,
+                incremental,
+            )
+            .await
+        }
+        Some(Command::Serve(ServeArgs {
+            listen,
+            webhook_secret,
+        })) => {
+            serve(
+                ServeState {
+                    client,
+                    repo_owner,
+                    repo_name,
+                    remote_path,
+                    local_path,
+                    incremental,
+                    webhook_secret,
+                },
+                listen,
+            )
+            .await
+        }
+    }
+}
+
+/// Download the object at `rev:remote_path` into `local_path`, recursing over
+/// trees. Shared by the one-shot CLI and the `serve` webhook handler.
+async fn run_sync(
+    client: &Client,
+    repo_owner: &str,
+    repo_name: &str,
+    rev: &str,
+    remote_path: &Utf8Path,
+    local_path: &Utf8Path,
+    incremental: bool,
+) -> anyhow::Result<()> {
     let start = client
         .query::<Start>(start::Variables {
-            repo_owner: repo_owner.clone(),
-            repo_name: repo_name.clone(),
-            rev_parse: format!("{}:{}", commit_ish, remote_path),
+            repo_owner: repo_owner.to_owned(),
+            repo_name: repo_name.to_owned(),
+            rev_parse: format!("{}:{}", rev, remote_path),
         })
         .await?
         .repository
         .context("no `repository` member")?;
     match start.object.context("no `object` member")? {
-        StartRepositoryObject::Blob(StartRepositoryObjectOnBlob { text }) => {
-            tokio::fs::write(local_path, text.context("binary blobs are not supported")?).await?;
+        StartRepositoryObject::Blob(StartRepositoryObjectOnBlob {
+            text,
+            is_binary,
+            oid,
+        }) => {
+            let bytes = match text {
+                Some(text) if !is_binary.unwrap_or(false) => text.into_bytes(),
+                _ => client.get_bytes(repo_owner, repo_name, &oid).await?,
+            };
+            tokio::fs::write(local_path, bytes).await?;
         }
         StartRepositoryObject::Tree(StartRepositoryObjectOnTree { entries }) => {
+            let incremental = match incremental {
+                true => Some(Incremental::load(local_path).await?),
+                false => None,
+            };
             tree(
-                local_path.into(),
-                entries
-                    .into_iter()
-                    .flatten()
-                    .map(|StartRepositoryObjectOnTreeEntries { name, oid }| (name, oid)),
-                &client,
-                &repo_name,
-                &repo_owner,
+                local_path.to_owned().into(),
+                entries.into_iter().flatten().map(
+                    |StartRepositoryObjectOnTreeEntries { name, oid, mode }| (name, oid, mode),
+                ),
+                client,
+                repo_name,
+                repo_owner,
+                incremental.as_ref(),
             )
             .await?;
+            if let Some(incremental) = incremental {
+                incremental.commit().await?;
+            }
         }
         StartRepositoryObject::Commit => bail!("unexpected `commit` object"),
         StartRepositoryObject::Tag => bail!("unexpected `tag` object"),
@@ -238,3 +679,144 @@ async fn _main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Shared configuration for the `serve` subcommand: the sync target plus the
+/// webhook secret used to authenticate incoming deliveries.
+struct ServeState {
+    client: Client,
+    repo_owner: String,
+    repo_name: String,
+    remote_path: Utf8PathBuf,
+    local_path: Utf8PathBuf,
+    incremental: bool,
+    webhook_secret: String,
+}
+
+/// Bind an HTTP listener and re-sync on every authenticated GitHub `push`.
+async fn serve(state: ServeState, listen: std::net::SocketAddr) -> anyhow::Result<()> {
+    let app = axum::Router::new()
+        .route("/", axum::routing::post(webhook))
+        .with_state(Arc::new(state));
+    let listener = tokio::net::TcpListener::bind(listen).await?;
+    println!("listening on {}", listener.local_addr()?);
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+async fn webhook(
+    axum::extract::State(state): axum::extract::State<Arc<ServeState>>,
+    headers: axum::http::HeaderMap,
+    body: axum::body::Bytes,
+) -> axum::http::StatusCode {
+    use axum::http::StatusCode;
+
+    let signature = headers
+        .get("x-hub-signature-256")
+        .and_then(|it| it.to_str().ok());
+    let authentic = signature
+        .is_some_and(|sig| verify_signature(state.webhook_secret.as_bytes(), &body, sig));
+    if !authentic {
+        return StatusCode::UNAUTHORIZED;
+    }
+
+    // Only `push` events carry a commit to sync against; ack everything else.
+    if headers.get("x-github-event").and_then(|it| it.to_str().ok()) != Some("push") {
+        return StatusCode::OK;
+    }
+    let event = match serde_json::from_slice::<PushEvent>(&body) {
+        Ok(it) => it,
+        Err(_) => return StatusCode::BAD_REQUEST,
+    };
+
+    match handle_push(&state, event).await {
+        Ok(()) => StatusCode::OK,
+        Err(e) => {
+            eprintln!("sync failed: {e:?}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// Re-sync when the push lands on a branch of the configured repository and
+/// touches the configured subtree.
+async fn handle_push(state: &ServeState, event: PushEvent) -> anyhow::Result<()> {
+    if !event.r#ref.starts_with("refs/heads/") {
+        return Ok(());
+    }
+    // Only mirror pushes for the repository this server was configured for; a
+    // validly-signed push for any other repo must not end up in `--local`.
+    if event.repository.owner.login != state.repo_owner
+        || event.repository.name != state.repo_name
+    {
+        return Ok(());
+    }
+    let prefix = state.remote_path.as_str().trim_start_matches('/');
+    let touches_subtree = event
+        .commits
+        .iter()
+        .chain(&event.head_commit)
+        .flat_map(|it| it.added.iter().chain(&it.removed).chain(&it.modified))
+        .any(|path| prefix.is_empty() || Utf8Path::new(path).starts_with(prefix));
+    if !touches_subtree {
+        return Ok(());
+    }
+
+    run_sync(
+        &state.client,
+        &state.repo_owner,
+        &state.repo_name,
+        &event.after,
+        &state.remote_path,
+        &state.local_path,
+        state.incremental,
+    )
+    .await
+}
+
+/// Constant-time verification of a `sha256=<hex>` HMAC signature over `body`.
+fn verify_signature(secret: &[u8], body: &[u8], header: &str) -> bool {
+    use hmac::{Hmac, Mac as _};
+    use sha2::Sha256;
+
+    let Some(hex_digest) = header.strip_prefix("sha256=") else {
+        return false;
+    };
+    let Ok(expected) = hex::decode(hex_digest) else {
+        return false;
+    };
+    let mut mac = <Hmac<Sha256>>::new_from_slice(secret).expect("HMAC accepts keys of any size");
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+#[derive(Deserialize)]
+struct PushEvent {
+    r#ref: String,
+    after: String,
+    repository: PushRepository,
+    #[serde(default)]
+    commits: Vec<PushCommit>,
+    #[serde(default)]
+    head_commit: Option<PushCommit>,
+}
+
+#[derive(Deserialize)]
+struct PushRepository {
+    name: String,
+    owner: PushOwner,
+}
+
+#[derive(Deserialize)]
+struct PushOwner {
+    login: String,
+}
+
+#[derive(Deserialize, Default)]
+struct PushCommit {
+    #[serde(default)]
+    added: Vec<String>,
+    #[serde(default)]
+    removed: Vec<String>,
+    #[serde(default)]
+    modified: Vec<String>,
+}